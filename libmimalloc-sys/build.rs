@@ -0,0 +1,36 @@
+use std::env;
+
+fn main() {
+    let mut build = cc::Build::new();
+
+    build.include("c_src/mimalloc/include");
+    build.include("c_src/mimalloc/src");
+    build.file("c_src/mimalloc/src/static.c");
+
+    // mimalloc is built as a static archive.
+    build.define("MI_STATIC_LIB", None);
+
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_vendor = env::var("CARGO_CFG_TARGET_VENDOR").unwrap_or_default();
+
+    if env::var_os("CARGO_FEATURE_OVERRIDE").is_some() {
+        build.define("MI_MALLOC_OVERRIDE", None);
+
+        // On Apple targets, zone registration alone is unreliable on recent
+        // macOS. When the interpose feature is enabled, build with
+        // `MI_OSX_INTERPOSE` so mimalloc also emits the `__DATA,__interpose`
+        // records that route libc's allocation symbols through mimalloc.
+        if target_vendor == "apple"
+            && env::var_os("CARGO_FEATURE_OVERRIDE_MACOS_INTERPOSE").is_some()
+        {
+            build.define("MI_OSX_INTERPOSE", None);
+            build.define("MI_OSX_ZONE", None);
+        }
+    }
+
+    if target_os == "linux" {
+        build.define("_GNU_SOURCE", None);
+    }
+
+    build.compile("mimalloc");
+}
@@ -0,0 +1,114 @@
+// Copyright 2019 Octavian Oncescu
+
+use core::ffi::{c_char, c_void};
+
+/// The signature of the output callback used by [`mi_stats_print_out`].
+///
+/// `msg` is a NUL-terminated chunk of the statistics report and `arg` is the
+/// opaque pointer originally handed to mimalloc.
+pub type mi_output_fun = unsafe extern "C" fn(msg: *const c_char, arg: *mut c_void);
+
+extern "C" {
+    /// Print the current allocation statistics through the `out` callback.
+    ///
+    /// When `out` is null, the statistics are written to mimalloc's default
+    /// output (usually `stderr`). `arg` is passed verbatim to `out`.
+    pub fn mi_stats_print_out(out: Option<mi_output_fun>, arg: *mut c_void);
+
+    /// Print the current allocation statistics to mimalloc's default output.
+    ///
+    /// `arg` is currently ignored and kept for backwards compatibility.
+    pub fn mi_stats_print(arg: *mut c_void);
+
+    /// Reset the allocation statistics.
+    pub fn mi_stats_reset();
+
+    /// Merge the thread-local statistics into the global statistics.
+    pub fn mi_stats_merge();
+}
+
+/// An opaque first-class heap.
+///
+/// Heaps are created with [`mi_heap_new`] and can allocate memory that is
+/// either freed individually or, more efficiently, reclaimed all at once with
+/// [`mi_heap_destroy`].
+#[repr(C)]
+pub struct mi_heap_t {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    /// Create a fresh heap owned by the calling thread.
+    pub fn mi_heap_new() -> *mut mi_heap_t;
+
+    /// Free all memory owned by `heap` and the heap itself.
+    ///
+    /// Unlike [`mi_heap_delete`], any blocks still allocated in `heap` are
+    /// released as well, so outstanding pointers into the heap must no longer
+    /// be used.
+    pub fn mi_heap_destroy(heap: *mut mi_heap_t);
+
+    /// Free the `heap` itself, migrating any still-live blocks to the backing
+    /// default heap.
+    pub fn mi_heap_delete(heap: *mut mi_heap_t);
+
+    /// Allocate `size` bytes in `heap`.
+    pub fn mi_heap_malloc(heap: *mut mi_heap_t, size: usize) -> *mut c_void;
+
+    /// Allocate zero-initialized `size` bytes in `heap`.
+    pub fn mi_heap_zalloc(heap: *mut mi_heap_t, size: usize) -> *mut c_void;
+
+    /// Allocate `size` bytes aligned by `alignment` in `heap`.
+    pub fn mi_heap_malloc_aligned(
+        heap: *mut mi_heap_t,
+        size: usize,
+        alignment: usize,
+    ) -> *mut c_void;
+
+    /// Re-allocate `p` (owned by `heap`) to `newsize` bytes.
+    pub fn mi_heap_realloc(heap: *mut mi_heap_t, p: *mut c_void, newsize: usize) -> *mut c_void;
+
+    /// Release free memory held by `heap` back to the OS; `force` also frees
+    /// retained pages.
+    pub fn mi_heap_collect(heap: *mut mi_heap_t, force: bool);
+}
+
+/// Stream the current allocation statistics to `f`.
+///
+/// mimalloc emits the report in chunks; each chunk is handed to `f` as a
+/// string slice. Chunks that are not valid UTF-8 are skipped.
+///
+/// ```rust,ignore
+/// let mut report = String::new();
+/// libmimalloc_sys::print_stats(|chunk| report.push_str(chunk));
+/// ```
+pub fn print_stats<F: FnMut(&str)>(mut f: F) {
+    unsafe extern "C" fn trampoline<F: FnMut(&str)>(msg: *const c_char, arg: *mut c_void) {
+        if msg.is_null() || arg.is_null() {
+            return;
+        }
+        let f = &mut *(arg as *mut F);
+        let bytes = core::ffi::CStr::from_ptr(msg).to_bytes();
+        if let Ok(s) = core::str::from_utf8(bytes) {
+            f(s);
+        }
+    }
+
+    unsafe {
+        mi_stats_print_out(Some(trampoline::<F>), &mut f as *mut F as *mut c_void);
+    }
+}
+
+/// Reset the allocation statistics.
+///
+/// Safe wrapper around [`mi_stats_reset`].
+pub fn reset_stats() {
+    unsafe { mi_stats_reset() }
+}
+
+/// Merge the thread-local statistics into the global statistics.
+///
+/// Safe wrapper around [`mi_stats_merge`].
+pub fn merge_stats() {
+    unsafe { mi_stats_merge() }
+}
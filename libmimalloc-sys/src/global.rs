@@ -0,0 +1,126 @@
+// Copyright 2019 Octavian Oncescu
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ffi::c_void;
+use core::mem;
+
+use super::*;
+
+/// A zero-sized handle to mimalloc suitable for use as Rust's global
+/// allocator.
+///
+/// ```rust,ignore
+/// use libmimalloc_sys::MiMalloc;
+///
+/// #[global_allocator]
+/// static ALLOC: MiMalloc = MiMalloc;
+/// ```
+///
+/// Small alignments (at most the natural alignment of a pointer) are served by
+/// the plain `mi_malloc` family, which is marginally cheaper than the aligned
+/// entry points; everything else goes through the `*_aligned` variants.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MiMalloc;
+
+unsafe impl GlobalAlloc for MiMalloc {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.align() <= mem::size_of::<usize>() {
+            mi_malloc(layout.size()) as *mut u8
+        } else {
+            mi_malloc_aligned(layout.size(), layout.align()) as *mut u8
+        }
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if layout.align() <= mem::size_of::<usize>() {
+            mi_zalloc(layout.size()) as *mut u8
+        } else {
+            mi_zalloc_aligned(layout.size(), layout.align()) as *mut u8
+        }
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // The `Layout` hands us the size and alignment back, so use the sized
+        // free path and let mimalloc skip its size-class reverse lookup.
+        mi_free_size_aligned(ptr as *mut c_void, layout.size(), layout.align());
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if layout.align() <= mem::size_of::<usize>() {
+            mi_realloc(ptr as *mut c_void, new_size) as *mut u8
+        } else {
+            mi_realloc_aligned(ptr as *mut c_void, new_size, layout.align()) as *mut u8
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+use core::alloc::{AllocError, Allocator};
+#[cfg(feature = "nightly")]
+use core::ptr::NonNull;
+
+#[cfg(feature = "nightly")]
+unsafe impl Allocator for MiMalloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = unsafe { GlobalAlloc::alloc(self, layout) };
+        let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = unsafe { GlobalAlloc::alloc_zeroed(self, layout) };
+        let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        GlobalAlloc::dealloc(self, ptr.as_ptr(), layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // When the alignment doesn't increase we can try to grow the block
+        // without moving it; `mi_expand` preserves the existing contents.
+        if new_layout.align() <= old_layout.align() {
+            let expanded = mi_expand(ptr.as_ptr() as *mut c_void, new_layout.size());
+            if let Some(p) = NonNull::new(expanded as *mut u8) {
+                return Ok(NonNull::slice_from_raw_parts(p, new_layout.size()));
+            }
+        }
+
+        // Otherwise fall back to a reallocation that copies as needed. The
+        // result must honor `new_layout`'s (possibly larger) alignment, so
+        // reallocate against it rather than `old_layout`.
+        let new = GlobalAlloc::realloc(self, ptr.as_ptr(), new_layout, new_layout.size());
+        let new = NonNull::new(new).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(new, new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // If the block already satisfies the smaller layout there is nothing to
+        // do: the existing alignment still holds and the usable size is enough.
+        if new_layout.align() <= old_layout.align()
+            && mi_usable_size(ptr.as_ptr() as *const c_void) >= new_layout.size()
+        {
+            return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+        }
+
+        // Reallocate against `new_layout` so the result honors its alignment.
+        let new = GlobalAlloc::realloc(self, ptr.as_ptr(), new_layout, new_layout.size());
+        let new = NonNull::new(new).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(new, new_layout.size()))
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2019 Octavian Oncescu
+
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+use crate::extended::*;
+
+/// A safe, owned handle to a first-class mimalloc heap.
+///
+/// A `Heap` is an isolated allocation pool. Memory allocated through it can be
+/// freed individually, but the real advantage is bulk reclamation: dropping the
+/// `Heap` releases every block it still owns in one call to
+/// [`mi_heap_destroy`], making it well suited to arena-style workloads.
+///
+/// A `Heap` is bound to the thread that created it and must not be shared
+/// across threads, hence it is neither `Send` nor `Sync`.
+pub struct Heap {
+    handle: NonNull<mi_heap_t>,
+}
+
+impl Heap {
+    /// Create a new, empty heap.
+    ///
+    /// Returns `None` if mimalloc could not create the heap.
+    pub fn new() -> Option<Heap> {
+        let handle = NonNull::new(unsafe { mi_heap_new() })?;
+        Some(Heap { handle })
+    }
+
+    /// Allocate `size` bytes in this heap.
+    ///
+    /// Returns null if out of memory.
+    #[inline]
+    pub fn malloc(&self, size: usize) -> *mut c_void {
+        unsafe { mi_heap_malloc(self.handle.as_ptr(), size) }
+    }
+
+    /// Allocate zero-initialized `size` bytes in this heap.
+    ///
+    /// Returns null if out of memory.
+    #[inline]
+    pub fn zalloc(&self, size: usize) -> *mut c_void {
+        unsafe { mi_heap_zalloc(self.handle.as_ptr(), size) }
+    }
+
+    /// Allocate `size` bytes aligned by `alignment` in this heap.
+    ///
+    /// Returns null if out of memory.
+    #[inline]
+    pub fn malloc_aligned(&self, size: usize, alignment: usize) -> *mut c_void {
+        unsafe { mi_heap_malloc_aligned(self.handle.as_ptr(), size, alignment) }
+    }
+
+    /// Re-allocate `p` (which must belong to this heap, or be null) to
+    /// `newsize` bytes.
+    ///
+    /// Returns null if out of memory, in which case `p` is not freed.
+    #[inline]
+    pub fn realloc(&self, p: *mut c_void, newsize: usize) -> *mut c_void {
+        unsafe { mi_heap_realloc(self.handle.as_ptr(), p, newsize) }
+    }
+
+    /// Release free memory held by this heap back to the OS.
+    ///
+    /// When `force` is set, retained pages are freed too.
+    #[inline]
+    pub fn collect(&self, force: bool) {
+        unsafe { mi_heap_collect(self.handle.as_ptr(), force) }
+    }
+}
+
+impl Drop for Heap {
+    fn drop(&mut self) {
+        unsafe { mi_heap_destroy(self.handle.as_ptr()) }
+    }
+}
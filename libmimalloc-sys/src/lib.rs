@@ -1,4 +1,5 @@
 #![no_std]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 // Copyright 2019 Octavian Oncescu
 
 use core::ffi::c_void;
@@ -10,6 +11,16 @@ mod extended;
 #[cfg(feature = "extended")]
 pub use extended::*;
 
+#[cfg(feature = "extended")]
+mod heap;
+#[cfg(feature = "extended")]
+pub use heap::Heap;
+
+#[cfg(feature = "global")]
+mod global;
+#[cfg(feature = "global")]
+pub use global::MiMalloc;
+
 extern "C" {
     /// Allocate zero-initialized `size` bytes.
     ///
@@ -65,6 +76,61 @@ extern "C" {
     ///
     /// The pointer `p` must have been allocated before (or be null).
     pub fn mi_free(p: *mut c_void);
+
+    /// Free previously allocated memory where the allocation `size` is known.
+    ///
+    /// Passing the original size back lets mimalloc skip the internal size-class
+    /// reverse lookup it would otherwise perform. The pointer `p` must have been
+    /// allocated before (or be null) and `size` must be the size requested at
+    /// allocation time.
+    pub fn mi_free_size(p: *mut c_void, size: usize);
+
+    /// Free previously allocated memory where the `size` and `alignment` are
+    /// known.
+    ///
+    /// As [`mi_free_size`], but also supplies the `alignment` the block was
+    /// allocated with. The pointer `p` must have been allocated before (or be
+    /// null).
+    pub fn mi_free_size_aligned(p: *mut c_void, size: usize, alignment: usize);
+
+    /// Try to grow or shrink the allocation at `p` in place to `newsize` bytes.
+    ///
+    /// Returns `p` unchanged if the block could be resized without moving,
+    /// otherwise returns null (in which case `p` is left untouched). Unlike
+    /// [`mi_realloc`], this never copies and never moves the allocation.
+    pub fn mi_expand(p: *mut c_void, newsize: usize) -> *mut c_void;
+
+    /// Return the available number of bytes in the allocation at `p`.
+    ///
+    /// This is at least the size requested at allocation time, but may be
+    /// larger because of mimalloc's size classes.
+    pub fn mi_usable_size(p: *const c_void) -> usize;
+
+    /// Return the number of bytes mimalloc would actually allocate for a
+    /// request of `size` bytes.
+    pub fn mi_good_size(size: usize) -> usize;
+}
+
+/// Return the actual number of bytes available in the allocation at `ptr`.
+///
+/// Thin wrapper around [`mi_usable_size`]. Unlike [`good_size`], this is an
+/// `unsafe fn`: `ptr` is handed to mimalloc unchecked.
+///
+/// # Safety
+///
+/// `ptr` must point to a block currently allocated by mimalloc (or be null).
+#[inline]
+pub unsafe fn usable_size(ptr: *const c_void) -> usize {
+    mi_usable_size(ptr)
+}
+
+/// Return the number of bytes mimalloc would allocate for a request of `size`
+/// bytes.
+///
+/// Safe wrapper around [`mi_good_size`].
+#[inline]
+pub fn good_size(size: usize) -> usize {
+    unsafe { mi_good_size(size) }
 }
 
 /// When using the `"override"` feature flag, the user wants us to globally
@@ -101,6 +167,13 @@ extern "C" {
 /// to explicitly reference something in the object file. The constructor
 /// symbol itself is static, so we can't get a reference to that, so instead
 /// we reference `mi_malloc` here too).
+///
+/// NOTE: On recent macOS (in particular Apple Silicon / M1), registering the
+/// zone alone is unreliable, so the `override_macos_interpose` feature builds
+/// mimalloc with `MI_OSX_INTERPOSE` (see `build.rs`). That emits the
+/// `__DATA,__interpose` records into the same object file as `mi_malloc`, so
+/// the `USED` reference above already keeps them alive — no extra `#[used]` can
+/// substitute for compiling the section in the first place.
 #[cfg(feature = "override")]
 mod set_up_statics {
     use super::*;
@@ -131,10 +204,121 @@ mod tests {
         unsafe { mi_free(ptr as *mut c_void) };
     }
 
+    #[cfg(feature = "global")]
+    #[test]
+    fn mimalloc_global_alloc_round_trip() {
+        use core::alloc::{GlobalAlloc, Layout};
+
+        let alloc = MiMalloc;
+
+        // Small alignment routes through the plain `mi_malloc` path and frees
+        // via the sized `dealloc`.
+        let small = Layout::from_size_align(64, 8).unwrap();
+        unsafe {
+            let ptr = alloc.alloc(small);
+            assert!(!ptr.is_null());
+            *ptr = 0x5a;
+            assert_eq!(*ptr, 0x5a);
+            alloc.dealloc(ptr, small);
+        }
+
+        // A larger alignment goes through the aligned path; `alloc_zeroed` must
+        // hand back zeroed, correctly aligned memory.
+        let aligned = Layout::from_size_align(128, 64).unwrap();
+        unsafe {
+            let ptr = alloc.alloc_zeroed(aligned);
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % 64, 0);
+            assert_eq!(*ptr, 0);
+            alloc.dealloc(ptr, aligned);
+        }
+    }
+
+    #[cfg(all(feature = "global", feature = "nightly"))]
+    #[test]
+    fn mimalloc_grow_and_shrink_preserve_contents() {
+        use core::alloc::{Allocator, Layout};
+
+        let alloc = MiMalloc;
+
+        let old = Layout::from_size_align(32, 8).unwrap();
+        let ptr = alloc.allocate(old).unwrap().cast::<u8>();
+        unsafe {
+            for i in 0..32 {
+                ptr.as_ptr().add(i).write(i as u8);
+            }
+        }
+
+        // Grow: the `mi_expand` fast path (or the realloc fallback) must return
+        // a block of at least the new size with the old bytes intact.
+        let bigger = Layout::from_size_align(64, 8).unwrap();
+        let grown = unsafe { alloc.grow(ptr, old, bigger) }.unwrap();
+        assert!(grown.len() >= 64);
+        let grown = grown.cast::<u8>();
+        unsafe {
+            for i in 0..32 {
+                assert_eq!(grown.as_ptr().add(i).read(), i as u8);
+            }
+        }
+
+        // Shrink: the usable-size short-circuit (or the realloc fallback) keeps
+        // the leading bytes and yields a conforming block.
+        let smaller = Layout::from_size_align(16, 8).unwrap();
+        let shrunk = unsafe { alloc.shrink(grown, bigger, smaller) }.unwrap();
+        assert!(shrunk.len() >= 16);
+        let shrunk = shrunk.cast::<u8>();
+        unsafe {
+            for i in 0..16 {
+                assert_eq!(shrunk.as_ptr().add(i).read(), i as u8);
+            }
+            alloc.deallocate(shrunk, smaller);
+        }
+    }
+
+    #[cfg(feature = "extended")]
+    #[test]
+    fn print_stats_streams_to_closure() {
+        extern crate std;
+        use std::string::String;
+
+        let mut report = String::new();
+        print_stats(|chunk| report.push_str(chunk));
+        assert!(!report.is_empty());
+    }
+
+    #[cfg(feature = "extended")]
+    #[test]
+    fn heap_allocates_and_drops() {
+        let heap = Heap::new().expect("failed to create heap");
+
+        let a = heap.malloc(64);
+        assert!(!a.is_null());
+        let b = heap.zalloc(128);
+        assert!(!b.is_null());
+        let c = heap.malloc_aligned(256, 64);
+        assert!(!c.is_null());
+        assert_eq!(c as usize % 64, 0);
+
+        // Dropping the heap reclaims `a`, `b` and `c` in bulk through
+        // `mi_heap_destroy`; no individual frees are needed.
+        drop(heap);
+    }
+
     #[cfg(all(feature = "override", target_vendor = "apple"))]
     #[test]
     fn mimalloc_and_libc_are_interoperable_when_overridden() {
+        // Zone registration: a block allocated by mimalloc must be recognised
+        // and freed by libc's `free`.
         let ptr = unsafe { mi_malloc(42) };
         unsafe { libc::free(ptr) };
+
+        // Interpose override: only once `MI_OSX_INTERPOSE` is compiled in does
+        // `libc::malloc` actually route through mimalloc, making it sound to
+        // free the result with `mi_free`.
+        #[cfg(feature = "override_macos_interpose")]
+        {
+            let ptr = unsafe { libc::malloc(42) };
+            unsafe { mi_free(ptr) };
+        }
     }
 }